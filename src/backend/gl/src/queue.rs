@@ -1013,10 +1013,20 @@ impl hal::queue::RawCommandQueue<Backend> for CommandQueue {
         Iw: IntoIterator<Item = &'a S>,
     {
         let gl = &self.share.context;
+        let srgb_color = self
+            .share
+            .legacy_features
+            .contains(LegacyFeatures::SRGB_COLOR);
 
         for swapchain in swapchains {
             let extent = swapchain.0.borrow().extent;
 
+            // Only convert to sRGB when blitting into the default framebuffer
+            // if the swapchain was configured with an sRGB format.
+            if srgb_color && !swapchain.0.borrow().srgb {
+                gl.disable(glow::FRAMEBUFFER_SRGB);
+            }
+
             gl.bind_framebuffer(glow::READ_FRAMEBUFFER, self.state.fbo);
             gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
             gl.blit_framebuffer(
@@ -1032,6 +1042,10 @@ impl hal::queue::RawCommandQueue<Backend> for CommandQueue {
                 glow::LINEAR,
             );
 
+            if srgb_color && !swapchain.0.borrow().srgb {
+                gl.enable(glow::FRAMEBUFFER_SRGB);
+            }
+
             swapchain.0.borrow().context.swap_buffers().unwrap();
         }
 
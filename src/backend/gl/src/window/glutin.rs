@@ -70,6 +70,8 @@ pub struct Swapchain {
     pub(crate) context: Starc<glutin::WindowedContext<glutin::PossiblyCurrent>>,
     // Extent because the window lies
     pub(crate) extent: Extent2D,
+    // Whether the default framebuffer should perform sRGB conversion on present
+    pub(crate) srgb: bool,
 }
 
 impl hal::Swapchain<B> for Swapchain {
@@ -107,6 +109,31 @@ impl Surface {
         &self.context
     }
 
+    /// Get the depth/stencil format of the window's default framebuffer, if it has one.
+    ///
+    /// The depth and stencil buffers are requested when building the glutin context,
+    /// e.g. with `ContextBuilder::with_depth_buffer`, and may differ from the request.
+    pub fn default_framebuffer_depth_stencil(&self) -> Option<f::Format> {
+        let pixel_format = self.context.get_pixel_format();
+        match (pixel_format.depth_bits, pixel_format.stencil_bits) {
+            (0, 0) => None,
+            (0, 8) => Some(f::Format::S8Uint),
+            (16, 0) => Some(f::Format::D16Unorm),
+            (24, 0) => Some(f::Format::X8D24Unorm),
+            (32, 0) => Some(f::Format::D32Sfloat),
+            (16, 8) => Some(f::Format::D16UnormS8Uint),
+            (24, 8) => Some(f::Format::D24UnormS8Uint),
+            (32, 8) => Some(f::Format::D32SfloatS8Uint),
+            (depth, stencil) => {
+                warn!(
+                    "Unknown default framebuffer depth/stencil bits: {}/{}",
+                    depth, stencil
+                );
+                None
+            }
+        }
+    }
+
     fn swapchain_formats(&self) -> Vec<f::Format> {
         let pixel_format = self.context.get_pixel_format();
         let color_bits = pixel_format.color_bits;
@@ -176,6 +203,7 @@ impl Device {
         let swapchain = Swapchain {
             extent: config.extent,
             context: surface.context.clone(),
+            srgb: config.format.base_format().1 == f::ChannelType::Srgb,
         };
 
         let gl = &self.share.context;
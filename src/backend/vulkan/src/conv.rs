@@ -21,6 +21,27 @@ pub fn map_vk_format(vk_format: vk::Format) -> Option<format::Format> {
     }
 }
 
+/// Size of a texel in bytes, or `None` for block-compressed formats.
+pub fn format_bytes_per_pixel(format: format::Format) -> Option<u32> {
+    let desc = format.surface_desc();
+    if desc.is_compressed() {
+        None
+    } else {
+        Some(desc.bits as u32 / 8)
+    }
+}
+
+/// Dimensions of the texel blocks, `(1, 1)` for uncompressed formats.
+pub fn block_dimensions(format: format::Format) -> (u32, u32) {
+    let (width, height) = format.surface_desc().dim;
+    (width as u32, height as u32)
+}
+
+/// Aspects covered by a full subresource range of an image in this format.
+pub fn format_aspects(format: format::Format) -> format::Aspects {
+    format.surface_desc().aspects
+}
+
 pub fn map_tiling(tiling: image::Tiling) -> vk::ImageTiling {
     vk::ImageTiling::from_raw(tiling as i32)
 }
@@ -560,8 +581,15 @@ pub fn map_present_mode(mode: PresentMode) -> vk::PresentModeKHR {
     vk::PresentModeKHR::from_raw(mode as i32)
 }
 
-pub fn map_vk_present_mode(mode: vk::PresentModeKHR) -> PresentMode {
-    unsafe { mem::transmute(mode) }
+pub fn map_vk_present_mode(mode: vk::PresentModeKHR) -> Option<PresentMode> {
+    match mode {
+        vk::PresentModeKHR::IMMEDIATE => Some(PresentMode::Immediate),
+        vk::PresentModeKHR::MAILBOX => Some(PresentMode::Mailbox),
+        vk::PresentModeKHR::FIFO => Some(PresentMode::Fifo),
+        vk::PresentModeKHR::FIFO_RELAXED => Some(PresentMode::Relaxed),
+        // Shared presentable image modes are not exposed
+        _ => None,
+    }
 }
 
 pub fn map_composite_alpha(composite_alpha: CompositeAlpha) -> vk::CompositeAlphaFlagsKHR {
@@ -577,3 +605,87 @@ pub fn map_descriptor_pool_create_flags(
 ) -> vk::DescriptorPoolCreateFlags {
     vk::DescriptorPoolCreateFlags::from_raw(flags.bits())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        block_dimensions,
+        format_aspects,
+        format_bytes_per_pixel,
+        map_format,
+        map_present_mode,
+        map_vk_format,
+        map_vk_present_mode,
+    };
+    use crate::hal::format::{Aspects, Format, NUM_FORMATS};
+    use crate::hal::PresentMode;
+    use ash::vk;
+
+    #[test]
+    fn test_format_round_trip() {
+        // HAL formats share their values with `vk::Format`, so every format
+        // survives the round trip, and anything outside the HAL range is dropped.
+        for raw in 1..NUM_FORMATS as i32 {
+            let vk_format = vk::Format::from_raw(raw);
+            let format = map_vk_format(vk_format).expect("Format dropped by the mapping");
+            assert_eq!(map_format(format), vk_format);
+        }
+        assert_eq!(map_vk_format(vk::Format::UNDEFINED), None);
+        assert_eq!(map_vk_format(vk::Format::from_raw(NUM_FORMATS as i32)), None);
+
+        // Common surface formats, including deep color ones, must stay in the HAL range.
+        let surface_formats = [
+            vk::Format::B8G8R8A8_UNORM,
+            vk::Format::B8G8R8A8_SRGB,
+            vk::Format::R8G8B8A8_UNORM,
+            vk::Format::R8G8B8A8_SRGB,
+            vk::Format::A2R10G10B10_UNORM_PACK32,
+            vk::Format::A2B10G10R10_UNORM_PACK32,
+            vk::Format::B10G11R11_UFLOAT_PACK32,
+            vk::Format::R16G16B16A16_SFLOAT,
+        ];
+        for &vk_format in surface_formats.iter() {
+            assert!(map_vk_format(vk_format).is_some(), "{:?}", vk_format);
+        }
+    }
+
+    #[test]
+    fn test_format_metadata() {
+        let formats = [
+            (Format::Bgra8Srgb, Some(4), (1, 1), Aspects::COLOR),
+            (Format::Rgba16Sfloat, Some(8), (1, 1), Aspects::COLOR),
+            (Format::A2r10g10b10Unorm, Some(4), (1, 1), Aspects::COLOR),
+            (Format::D32Sfloat, Some(4), (1, 1), Aspects::DEPTH),
+            (
+                Format::D24UnormS8Uint,
+                Some(4),
+                (1, 1),
+                Aspects::DEPTH | Aspects::STENCIL,
+            ),
+            (Format::Bc1RgbaUnorm, None, (4, 4), Aspects::COLOR),
+            (Format::Astc8x6Srgb, None, (8, 6), Aspects::COLOR),
+        ];
+        for &(format, bytes, dim, aspects) in formats.iter() {
+            assert_eq!(format_bytes_per_pixel(format), bytes, "{:?}", format);
+            assert_eq!(block_dimensions(format), dim, "{:?}", format);
+            assert_eq!(format_aspects(format), aspects, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_present_mode_round_trip() {
+        let modes = [
+            PresentMode::Immediate,
+            PresentMode::Mailbox,
+            PresentMode::Fifo,
+            PresentMode::Relaxed,
+        ];
+        for &mode in modes.iter() {
+            assert_eq!(map_vk_present_mode(map_present_mode(mode)), Some(mode));
+        }
+        assert_eq!(
+            map_vk_present_mode(vk::PresentModeKHR::SHARED_DEMAND_REFRESH),
+            None
+        );
+    }
+}
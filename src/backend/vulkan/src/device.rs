@@ -1935,7 +1935,7 @@ impl d::Device<B> for Device {
     unsafe fn create_swapchain(
         &self,
         surface: &mut w::Surface,
-        config: SwapchainConfig,
+        mut config: SwapchainConfig,
         provided_old_swapchain: Option<w::Swapchain>,
     ) -> Result<(w::Swapchain, Vec<n::Image>), hal::window::CreationError> {
         let functor = khr::Swapchain::new(&surface.raw.instance.0, &self.raw.0);
@@ -1945,69 +1945,128 @@ impl d::Device<B> for Device {
             None => vk::SwapchainKHR::null(),
         };
 
-        surface.width = config.extent.width;
-        surface.height = config.extent.height;
+        fn map_error(result: vk::Result) -> hal::window::CreationError {
+            match result {
+                vk::Result::ERROR_OUT_OF_HOST_MEMORY => d::OutOfMemory::OutOfHostMemory.into(),
+                vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
+                    d::OutOfMemory::OutOfDeviceMemory.into()
+                }
+                vk::Result::ERROR_DEVICE_LOST => d::DeviceLost.into(),
+                vk::Result::ERROR_SURFACE_LOST_KHR => d::SurfaceLost.into(),
+                vk::Result::ERROR_NATIVE_WINDOW_IN_USE_KHR => d::WindowInUse.into(),
+                vk::Result::ERROR_OUT_OF_DATE_KHR => hal::window::CreationError::OutOfDate,
+                _ => unreachable!("Unexpected result - driver bug? {:?}", result),
+            }
+        }
 
-        let info = vk::SwapchainCreateInfoKHR {
-            s_type: vk::StructureType::SWAPCHAIN_CREATE_INFO_KHR,
-            p_next: ptr::null(),
-            flags: vk::SwapchainCreateFlagsKHR::empty(),
-            surface: surface.raw.handle,
-            min_image_count: config.image_count,
-            image_format: conv::map_format(config.format),
-            image_color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
-            image_extent: vk::Extent2D {
-                width: surface.width,
-                height: surface.height,
-            },
-            image_array_layers: 1,
-            image_usage: conv::map_image_usage(config.image_usage),
-            image_sharing_mode: vk::SharingMode::EXCLUSIVE,
-            queue_family_index_count: 0,
-            p_queue_family_indices: ptr::null(),
-            pre_transform: vk::SurfaceTransformFlagsKHR::IDENTITY,
-            composite_alpha: conv::map_composite_alpha(config.composite_alpha),
-            present_mode: conv::map_present_mode(config.present_mode),
-            clipped: 1,
-            old_swapchain,
-        };
+        // Every path out of here must leave the old swapchain to be destroyed below.
+        let mut create = || -> Result<vk::SwapchainKHR, hal::window::CreationError> {
+            let mut attempt = 0;
+            loop {
+                // Query once per attempt, the surface may have changed since the last one.
+                let caps = surface.capabilities_raw(self.raw.2).map_err(map_error)?;
+                let supported_alpha = conv::map_vk_composite_alpha(caps.supported_composite_alpha);
+                if !supported_alpha.contains(config.composite_alpha) {
+                    return Err(hal::window::CreationError::UnsupportedCompositeAlpha(
+                        config.composite_alpha,
+                    ));
+                }
 
-        let result = functor.create_swapchain(&info, None);
+                // Avoid an extra rotation pass in the compositor on pre-rotated displays,
+                // unless the application opted out.
+                let pre_transform = match surface.pre_transform {
+                    Some(transform) if caps.supported_transforms.contains(transform) => transform,
+                    Some(transform) => {
+                        warn!(
+                            "Surface transform {:?} is not supported, using {:?}",
+                            transform, caps.current_transform
+                        );
+                        caps.current_transform
+                    }
+                    None => caps.current_transform,
+                };
+
+                let extent = w::clamp_extent(&caps, config.extent);
+                if extent != config.extent {
+                    warn!(
+                        "Swapchain extent {:?} is not supported by the surface, using {:?}",
+                        config.extent, extent
+                    );
+                    config.extent = extent;
+                }
+
+                surface.width = config.extent.width;
+                surface.height = config.extent.height;
+
+                let info = vk::SwapchainCreateInfoKHR {
+                    s_type: vk::StructureType::SWAPCHAIN_CREATE_INFO_KHR,
+                    p_next: ptr::null(),
+                    flags: vk::SwapchainCreateFlagsKHR::empty(),
+                    surface: surface.raw.handle,
+                    min_image_count: config.image_count,
+                    image_format: conv::map_format(config.format),
+                    image_color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
+                    image_extent: vk::Extent2D {
+                        width: surface.width,
+                        height: surface.height,
+                    },
+                    image_array_layers: config.image_layers as _,
+                    image_usage: conv::map_image_usage(config.image_usage),
+                    image_sharing_mode: vk::SharingMode::EXCLUSIVE,
+                    queue_family_index_count: 0,
+                    p_queue_family_indices: ptr::null(),
+                    pre_transform,
+                    composite_alpha: conv::map_composite_alpha(config.composite_alpha),
+                    present_mode: conv::map_present_mode(config.present_mode),
+                    clipped: 1,
+                    // The old swapchain is retired by the first attempt, even if it fails.
+                    old_swapchain: if attempt == 0 {
+                        old_swapchain
+                    } else {
+                        vk::SwapchainKHR::null()
+                    },
+                };
+
+                match functor.create_swapchain(&info, None) {
+                    Err(vk::Result::ERROR_OUT_OF_DATE_KHR)
+                        if attempt < surface.swapchain_retries =>
+                    {
+                        attempt += 1;
+                        warn!(
+                            "Surface changed during swapchain creation, retrying ({}/{})",
+                            attempt, surface.swapchain_retries
+                        );
+                    }
+                    result => return result.map_err(map_error),
+                }
+            }
+        };
+        let result = create();
 
         if old_swapchain != vk::SwapchainKHR::null() {
             functor.destroy_swapchain(old_swapchain, None)
         }
 
-        let swapchain_raw = match result {
-            Ok(swapchain_raw) => swapchain_raw,
-            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => {
-                return Err(d::OutOfMemory::OutOfHostMemory.into());
-            }
-            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
-                return Err(d::OutOfMemory::OutOfDeviceMemory.into());
-            }
-            Err(vk::Result::ERROR_DEVICE_LOST) => return Err(d::DeviceLost.into()),
-            Err(vk::Result::ERROR_SURFACE_LOST_KHR) => return Err(d::SurfaceLost.into()),
-            Err(vk::Result::ERROR_NATIVE_WINDOW_IN_USE_KHR) => return Err(d::WindowInUse.into()),
-            _ => unreachable!("Unexpected result - driver bug? {:?}", result),
-        };
+        let swapchain_raw = result?;
 
         let result = functor.get_swapchain_images(swapchain_raw);
 
         let backbuffer_images = match result {
             Ok(backbuffer_images) => backbuffer_images,
-            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => {
-                return Err(d::OutOfMemory::OutOfHostMemory.into());
-            }
-            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
-                return Err(d::OutOfMemory::OutOfDeviceMemory.into());
+            Err(err) => {
+                functor.destroy_swapchain(swapchain_raw, None);
+                return Err(map_error(err));
             }
-            _ => unreachable!(),
         };
 
         let swapchain = w::Swapchain {
             raw: swapchain_raw,
             functor,
+            config,
+            device: self.raw.clone(),
+            suboptimal_policy: w::SuboptimalPolicy::default(),
+            recreate_pending: false,
+            default_acquire_timeout: !0,
         };
 
         let images = backbuffer_images
@@ -13,6 +13,8 @@ extern crate lazy_static;
 #[cfg(feature = "use-rtld-next")]
 extern crate shared_library;
 extern crate smallvec;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 
 #[cfg(target_os = "macos")]
 extern crate core_graphics;
@@ -65,6 +67,19 @@ mod pool;
 mod result;
 mod window;
 
+pub use crate::window::{
+    CancellableAcquireError,
+    ExtentMismatch,
+    FullScreenExclusiveSupport,
+    PresentAndAcquireError,
+    PresentProfile,
+    SuboptimalPolicy,
+    SuboptimalReason,
+    SurfaceConfigOption,
+    SurfaceCreationError,
+    TransientTargetError,
+};
+
 // CStr's cannot be constant yet, until const fn lands we need to use a lazy_static
 lazy_static! {
     static ref LAYERS: Vec<&'static CStr> = if cfg!(all(target_os = "android", debug_assertions)) {
@@ -416,10 +431,22 @@ impl Instance {
         #[cfg(not(debug_assertions))]
         let debug_messenger = None;
 
-        Instance {
+        let instance = Instance {
             raw: Arc::new(RawInstance(instance, debug_messenger)),
             extensions,
-        }
+        };
+        info!("Enabled surface extensions: {:?}", instance.surface_extensions());
+        instance
+    }
+
+    /// List the enabled extensions related to surfaces and presentation,
+    /// e.g. for diagnosing why a window system isn't supported.
+    pub fn surface_extensions(&self) -> Vec<&'static CStr> {
+        self.extensions
+            .iter()
+            .cloned()
+            .filter(|ext| SURFACE_EXTENSIONS.contains(ext))
+            .collect()
     }
 }
 
@@ -457,19 +484,7 @@ impl hal::Instance for Instance {
                     handle: device,
                     properties,
                 };
-                let queue_families = unsafe {
-                    self.raw
-                        .0
-                        .get_physical_device_queue_family_properties(device)
-                        .into_iter()
-                        .enumerate()
-                        .map(|(i, properties)| QueueFamily {
-                            properties,
-                            device,
-                            index: i as u32,
-                        })
-                        .collect()
-                };
+                let queue_families = physical_device.queue_families();
 
                 hal::Adapter {
                     info,
@@ -509,6 +524,54 @@ pub struct PhysicalDevice {
     properties: vk::PhysicalDeviceProperties,
 }
 
+impl PhysicalDevice {
+    fn queue_families(&self) -> Vec<QueueFamily> {
+        unsafe {
+            self.instance
+                .0
+                .get_physical_device_queue_family_properties(self.handle)
+                .into_iter()
+                .enumerate()
+                .map(|(i, properties)| QueueFamily {
+                    properties,
+                    device: self.handle,
+                    index: i as u32,
+                })
+                .collect()
+        }
+    }
+
+    /// List the queue families of this physical device along with
+    /// whether they can present to the given surface.
+    pub fn present_support(&self, surface: &window::Surface) -> Vec<(QueueFamily, bool)> {
+        self.queue_families()
+            .into_iter()
+            .map(|family| {
+                let supported = hal::Surface::supports_queue_family(surface, &family);
+                (family, supported)
+            })
+            .collect()
+    }
+
+    /// Check if the physical device exposes the given device extension.
+    pub(crate) fn supports_extension(&self, name: &CStr) -> bool {
+        let extensions = unsafe {
+            self.instance
+                .0
+                .enumerate_device_extension_properties(self.handle)
+        };
+        match extensions {
+            Ok(extensions) => extensions.iter().any(|ext| unsafe {
+                CStr::from_ptr(ext.extension_name.as_ptr()) == name
+            }),
+            Err(err) => {
+                error!("Unable to enumerate device extensions: {:?}", err);
+                false
+            }
+        }
+    }
+}
+
 impl hal::PhysicalDevice<Backend> for PhysicalDevice {
     unsafe fn open(
         &self,
@@ -571,7 +634,7 @@ impl hal::PhysicalDevice<Backend> for PhysicalDevice {
         });
 
         let device = Device {
-            raw: Arc::new(RawDevice(device_raw, requested_features)),
+            raw: Arc::new(RawDevice(device_raw, requested_features, self.handle)),
         };
 
         let device_arc = device.raw.clone();
@@ -926,7 +989,7 @@ impl hal::PhysicalDevice<Backend> for PhysicalDevice {
 }
 
 #[doc(hidden)]
-pub struct RawDevice(pub ash::Device, Features);
+pub struct RawDevice(pub ash::Device, Features, vk::PhysicalDevice);
 impl fmt::Debug for RawDevice {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "RawDevice") // TODO: Real Debug impl
@@ -1017,6 +1080,11 @@ impl hal::queue::RawCommandQueue<Backend> for CommandQueue {
         S: 'a + Borrow<native::Semaphore>,
         Iw: IntoIterator<Item = &'a S>,
     {
+        #[cfg(feature = "tracing")]
+        let span = tracing::trace_span!("present");
+        #[cfg(feature = "tracing")]
+        let _guard = span.enter();
+
         let semaphores = wait_semaphores
             .into_iter()
             .map(|sem| sem.borrow().0)
@@ -1040,7 +1108,15 @@ impl hal::queue::RawCommandQueue<Backend> for CommandQueue {
             p_results: ptr::null_mut(),
         };
 
-        match self.swapchain_fn.queue_present_khr(*self.raw, &info) {
+        let result = self.swapchain_fn.queue_present_khr(*self.raw, &info);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            image_indices = ?frames,
+            suboptimal = result == vk::Result::SUBOPTIMAL_KHR,
+            "presented"
+        );
+
+        match result {
             vk::Result::SUCCESS => Ok(None),
             vk::Result::SUBOPTIMAL_KHR => Ok(Some(Suboptimal)),
             vk::Result::ERROR_OUT_OF_HOST_MEMORY => Err(PresentError::OutOfMemory(OutOfMemory::OutOfHostMemory)),
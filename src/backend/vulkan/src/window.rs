@@ -1,9 +1,15 @@
+use std::ffi::CStr;
+use std::iter;
+use std::mem;
 use std::os::raw::c_void;
 use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use ash::extensions::khr;
+use ash::version::{EntryV1_0, InstanceV1_0};
 use ash::vk;
+use smallvec::SmallVec;
 
 use crate::hal;
 use crate::hal::format::Format;
@@ -12,8 +18,19 @@ use crate::hal::image::{NumSamples, Size};
 #[cfg(feature = "winit")]
 use winit;
 
+use crate::command::CommandBuffer;
 use crate::{conv, native};
-use crate::{Backend, Instance, PhysicalDevice, QueueFamily, RawInstance, VK_ENTRY};
+use crate::{
+    Backend,
+    CommandQueue,
+    Device,
+    Instance,
+    PhysicalDevice,
+    QueueFamily,
+    RawDevice,
+    RawInstance,
+    VK_ENTRY,
+};
 
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -25,18 +42,144 @@ pub struct Surface {
     pub(crate) width: Size,
     pub(crate) height: Size,
     pub(crate) samples: NumSamples,
+    // Number of times swapchain creation is retried if the surface changes meanwhile.
+    pub(crate) swapchain_retries: u32,
+    // Transform swapchains are created with, `None` for the current surface transform.
+    pub(crate) pre_transform: Option<vk::SurfaceTransformFlagsKHR>,
+    // The `CAMetalLayer` backing the `NSView` the surface was created from, if any.
+    #[cfg(target_os = "macos")]
+    #[derivative(Debug = "ignore")]
+    pub(crate) metal_layer: Option<MetalLayer>,
+}
+
+/// A retained `CAMetalLayer`, released on drop.
+#[cfg(target_os = "macos")]
+pub(crate) struct MetalLayer(ptr::NonNull<objc::runtime::Object>);
+
+// Unlike `NSView`, Core Animation layers may be used from any thread.
+#[cfg(target_os = "macos")]
+unsafe impl Send for MetalLayer {}
+#[cfg(target_os = "macos")]
+unsafe impl Sync for MetalLayer {}
+
+#[cfg(target_os = "macos")]
+impl Drop for MetalLayer {
+    fn drop(&mut self) {
+        unsafe {
+            msg_send![self.0.as_ptr(), release];
+        }
+    }
 }
 
 pub struct RawSurface {
     pub(crate) handle: vk::SurfaceKHR,
     functor: khr::Surface,
+    // Raw entry points, for queries the functor only exposes allocating wrappers for.
+    surface_fn: vk::KhrSurfaceFn,
     pub(crate) instance: Arc<RawInstance>,
+    // Surfaces provided by external libraries are destroyed by their owner.
+    owned: bool,
+}
+
+impl RawSurface {
+    fn new(handle: vk::SurfaceKHR, instance: Arc<RawInstance>, owned: bool) -> Self {
+        let entry = VK_ENTRY
+            .as_ref()
+            .expect("Unable to load Vulkan entry points");
+        let surface_fn = vk::KhrSurfaceFn::load(|name| unsafe {
+            mem::transmute(entry.get_instance_proc_addr(instance.0.handle(), name.as_ptr()))
+        });
+
+        RawSurface {
+            handle,
+            functor: khr::Surface::new(entry, &instance.0),
+            surface_fn,
+            instance,
+            owned,
+        }
+    }
 }
 
 impl Drop for RawSurface {
     fn drop(&mut self) {
-        unsafe {
-            self.functor.destroy_surface(self.handle, None);
+        if self.owned {
+            unsafe {
+                self.functor.destroy_surface(self.handle, None);
+            }
+        }
+    }
+}
+
+/// Error occurred during surface creation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SurfaceCreationError {
+    /// The WSI extension required for this kind of window is not supported by the driver.
+    MissingExtension(&'static CStr),
+    /// Out of either host or device memory.
+    OutOfMemory(hal::device::OutOfMemory),
+    /// The native window is already in use by another API.
+    WindowInUse(hal::device::WindowInUse),
+    /// The properties of the native window could not be queried.
+    InvalidWindow,
+    /// None of the window systems supported by the platform are available.
+    NoSupportedWsi,
+}
+
+/// The swapchain extent doesn't match the one expected by the application.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExtentMismatch {
+    /// Extent the application expected, e.g. the size of its framebuffers.
+    pub expected: hal::window::Extent2D,
+    /// Extent the swapchain was created with.
+    pub actual: hal::window::Extent2D,
+}
+
+/// Error returned by `Swapchain::acquire_image_cancellable`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CancellableAcquireError {
+    /// The acquisition was cancelled by the application before an image was ready.
+    Cancelled,
+    /// The acquisition itself failed.
+    Acquire(hal::AcquireError),
+}
+
+/// Error returned by `Swapchain::present_and_acquire`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresentAndAcquireError {
+    /// The image couldn't be presented, and no image was acquired.
+    Present(hal::window::PresentError),
+    /// The image was presented, but the next one couldn't be acquired.
+    Acquire(hal::AcquireError),
+}
+
+/// Error returned by `Swapchain::create_transient_msaa_target`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransientTargetError {
+    /// The image couldn't be created, e.g. because the sample count isn't supported.
+    Image(hal::image::CreationError),
+    /// None of the device local memory types can back the image.
+    NoMemoryType,
+    /// The memory backing the image couldn't be allocated.
+    Allocation(hal::device::AllocationError),
+    /// The memory couldn't be bound to the image.
+    Bind(hal::device::BindError),
+    /// The view of the image couldn't be created.
+    View(hal::image::ViewError),
+}
+
+impl From<vk::Result> for SurfaceCreationError {
+    fn from(result: vk::Result) -> Self {
+        match result {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => {
+                SurfaceCreationError::OutOfMemory(hal::device::OutOfMemory::OutOfHostMemory)
+            }
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
+                SurfaceCreationError::OutOfMemory(hal::device::OutOfMemory::OutOfDeviceMemory)
+            }
+            vk::Result::ERROR_NATIVE_WINDOW_IN_USE_KHR => {
+                SurfaceCreationError::WindowInUse(hal::device::WindowInUse)
+            }
+            _ => unreachable!("Unexpected result - driver bug? {:?}", result),
         }
     }
 }
@@ -44,14 +187,36 @@ impl Drop for RawSurface {
 impl Instance {
     #[cfg(all(feature = "x11", unix, not(target_os = "android"), not(target_os = "macos")))]
     pub fn create_surface_from_xlib(&self, dpy: *mut vk::Display, window: vk::Window) -> Surface {
+        self.try_create_surface_from_xlib(dpy, window)
+            .expect("Unable to create Xlib surface")
+    }
+
+    #[cfg(all(feature = "x11", unix, not(target_os = "android"), not(target_os = "macos")))]
+    pub fn try_create_surface_from_xlib(
+        &self,
+        dpy: *mut vk::Display,
+        window: vk::Window,
+    ) -> Result<Surface, SurfaceCreationError> {
         let entry = VK_ENTRY
             .as_ref()
             .expect("Unable to load Vulkan entry points");
 
         if !self.extensions.contains(&khr::XlibSurface::name()) {
-            panic!("Vulkan driver does not support VK_KHR_XLIB_SURFACE");
+            return Err(SurfaceCreationError::MissingExtension(khr::XlibSurface::name()));
         }
 
+        let (width, height) = unsafe {
+            use std::mem::zeroed;
+            use x11::xlib::{XGetWindowAttributes, XWindowAttributes};
+            let mut attribs: XWindowAttributes = zeroed();
+            let result = XGetWindowAttributes(dpy as _, window, &mut attribs);
+            if result == 0 {
+                error!("XGetWindowAttributes failed");
+                return Err(SurfaceCreationError::InvalidWindow);
+            }
+            (attribs.width as Size, attribs.height as Size)
+        };
+
         let surface = {
             let xlib_loader = khr::XlibSurface::new(entry, &self.raw.0);
             let info = vk::XlibSurfaceCreateInfoKHR {
@@ -62,22 +227,10 @@ impl Instance {
                 dpy,
             };
 
-            unsafe { xlib_loader.create_xlib_surface(&info, None) }
-                .expect("XlibSurface::create_xlib_surface() failed")
-        };
-
-        let (width, height) = unsafe {
-            use std::mem::zeroed;
-            use x11::xlib::{XGetWindowAttributes, XWindowAttributes};
-            let mut attribs: XWindowAttributes = zeroed();
-            let result = XGetWindowAttributes(dpy as _, window, &mut attribs);
-            if result == 0 {
-                panic!("XGetGeometry failed");
-            }
-            (attribs.width as Size, attribs.height as Size)
+            unsafe { xlib_loader.create_xlib_surface(&info, None) }?
         };
 
-        self.create_surface_from_vk_surface_khr(surface, width, height, 1)
+        Ok(self.create_surface_from_vk_surface_khr(surface, width, height, 1))
     }
 
     #[cfg(all(feature = "xcb", unix, not(target_os = "android"), not(target_os = "macos")))]
@@ -86,14 +239,39 @@ impl Instance {
         connection: *mut vk::xcb_connection_t,
         window: vk::xcb_window_t,
     ) -> Surface {
+        self.try_create_surface_from_xcb(connection, window)
+            .expect("Unable to create XCB surface")
+    }
+
+    #[cfg(all(feature = "xcb", unix, not(target_os = "android"), not(target_os = "macos")))]
+    pub fn try_create_surface_from_xcb(
+        &self,
+        connection: *mut vk::xcb_connection_t,
+        window: vk::xcb_window_t,
+    ) -> Result<Surface, SurfaceCreationError> {
         let entry = VK_ENTRY
             .as_ref()
             .expect("Unable to load Vulkan entry points");
 
         if !self.extensions.contains(&khr::XcbSurface::name()) {
-            panic!("Vulkan driver does not support VK_KHR_XCB_SURFACE");
+            return Err(SurfaceCreationError::MissingExtension(khr::XcbSurface::name()));
         }
 
+        let (width, height) = unsafe {
+            use std::mem;
+            use xcb::{xproto, Connection};
+            let conn = Connection::from_raw_conn(connection as _);
+            let geometry = xproto::get_geometry(&conn, window).get_reply();
+            mem::forget(conn); //TODO: use `into_raw_conn`
+            match geometry {
+                Ok(geometry) => (geometry.width() as _, geometry.height() as _),
+                Err(_) => {
+                    error!("xcb_get_geometry failed");
+                    return Err(SurfaceCreationError::InvalidWindow);
+                }
+            }
+        };
+
         let surface = {
             let xcb_loader = khr::XcbSurface::new(entry, &self.raw.0);
             let info = vk::XcbSurfaceCreateInfoKHR {
@@ -104,25 +282,10 @@ impl Instance {
                 connection,
             };
 
-            unsafe { xcb_loader.create_xcb_surface(&info, None) }
-                .expect("XcbSurface::create_xcb_surface() failed")
-        };
-
-        let (width, height) = unsafe {
-            use std::mem;
-            use xcb::{xproto, Connection};
-            let conn = Connection::from_raw_conn(connection as _);
-            let geometry = xproto::get_geometry(&conn, window)
-                .get_reply()
-                .expect("xcb_get_geometry failed")
-                .ptr
-                .as_ref()
-                .expect("unexpected NULL XCB geometry");
-            mem::forget(conn); //TODO: use `into_raw_conn`
-            (geometry.width as _, geometry.height as _)
+            unsafe { xcb_loader.create_xcb_surface(&info, None) }?
         };
 
-        self.create_surface_from_vk_surface_khr(surface, width, height, 1)
+        Ok(self.create_surface_from_vk_surface_khr(surface, width, height, 1))
     }
 
     #[cfg(all(unix, not(target_os = "android")))]
@@ -133,12 +296,24 @@ impl Instance {
         width: Size,
         height: Size,
     ) -> Surface {
+        self.try_create_surface_from_wayland(display, surface, width, height)
+            .expect("Unable to create Wayland surface")
+    }
+
+    #[cfg(all(unix, not(target_os = "android")))]
+    pub fn try_create_surface_from_wayland(
+        &self,
+        display: *mut c_void,
+        surface: *mut c_void,
+        width: Size,
+        height: Size,
+    ) -> Result<Surface, SurfaceCreationError> {
         let entry = VK_ENTRY
             .as_ref()
             .expect("Unable to load Vulkan entry points");
 
         if !self.extensions.contains(&khr::WaylandSurface::name()) {
-            panic!("Vulkan driver does not support VK_KHR_WAYLAND_SURFACE");
+            return Err(SurfaceCreationError::MissingExtension(khr::WaylandSurface::name()));
         }
 
         let surface = {
@@ -151,10 +326,10 @@ impl Instance {
                 surface: surface as *mut _,
             };
 
-            unsafe { w_loader.create_wayland_surface(&info, None) }.expect("WaylandSurface failed")
+            unsafe { w_loader.create_wayland_surface(&info, None) }?
         };
 
-        self.create_surface_from_vk_surface_khr(surface, width, height, 1)
+        Ok(self.create_surface_from_vk_surface_khr(surface, width, height, 1))
     }
 
     #[cfg(target_os = "android")]
@@ -164,6 +339,17 @@ impl Instance {
         width: Size,
         height: Size,
     ) -> Surface {
+        self.try_create_surface_android(window, width, height)
+            .expect("Unable to create Android surface")
+    }
+
+    #[cfg(target_os = "android")]
+    pub fn try_create_surface_android(
+        &self,
+        window: *const c_void,
+        width: Size,
+        height: Size,
+    ) -> Result<Surface, SurfaceCreationError> {
         let entry = VK_ENTRY
             .as_ref()
             .expect("Unable to load Vulkan entry points");
@@ -177,38 +363,32 @@ impl Instance {
                 window: window as *const _ as *mut _,
             };
 
-            unsafe { loader.create_android_surface(&info, None) }.expect("AndroidSurface failed")
+            unsafe { loader.create_android_surface(&info, None) }?
         };
 
-        self.create_surface_from_vk_surface_khr(surface, width, height, 1)
+        Ok(self.create_surface_from_vk_surface_khr(surface, width, height, 1))
     }
 
     #[cfg(windows)]
     pub fn create_surface_from_hwnd(&self, hinstance: *mut c_void, hwnd: *mut c_void) -> Surface {
+        self.try_create_surface_from_hwnd(hinstance, hwnd)
+            .expect("Unable to create Win32 surface")
+    }
+
+    #[cfg(windows)]
+    pub fn try_create_surface_from_hwnd(
+        &self,
+        hinstance: *mut c_void,
+        hwnd: *mut c_void,
+    ) -> Result<Surface, SurfaceCreationError> {
         let entry = VK_ENTRY
             .as_ref()
             .expect("Unable to load Vulkan entry points");
 
         if !self.extensions.contains(&khr::Win32Surface::name()) {
-            panic!("Vulkan driver does not support VK_KHR_WIN32_SURFACE");
+            return Err(SurfaceCreationError::MissingExtension(khr::Win32Surface::name()));
         }
 
-        let surface = {
-            let info = vk::Win32SurfaceCreateInfoKHR {
-                s_type: vk::StructureType::WIN32_SURFACE_CREATE_INFO_KHR,
-                p_next: ptr::null(),
-                flags: vk::Win32SurfaceCreateFlagsKHR::empty(),
-                hinstance: hinstance as *mut _,
-                hwnd: hwnd as *mut _,
-            };
-            let win32_loader = khr::Win32Surface::new(entry, &self.raw.0);
-            unsafe {
-                win32_loader
-                    .create_win32_surface(&info, None)
-                    .expect("Unable to create Win32 surface")
-            }
-        };
-
         let (width, height) = unsafe {
             use std::mem::zeroed;
             use winapi::shared::windef::RECT;
@@ -216,7 +396,8 @@ impl Instance {
 
             let mut rect: RECT = zeroed();
             if GetClientRect(hwnd as *mut _, &mut rect as *mut RECT) == 0 {
-                panic!("GetClientRect failed");
+                error!("GetClientRect failed");
+                return Err(SurfaceCreationError::InvalidWindow);
             }
             (
                 (rect.right - rect.left) as Size,
@@ -224,11 +405,32 @@ impl Instance {
             )
         };
 
-        self.create_surface_from_vk_surface_khr(surface, width, height, 1)
+        let surface = {
+            let info = vk::Win32SurfaceCreateInfoKHR {
+                s_type: vk::StructureType::WIN32_SURFACE_CREATE_INFO_KHR,
+                p_next: ptr::null(),
+                flags: vk::Win32SurfaceCreateFlagsKHR::empty(),
+                hinstance: hinstance as *mut _,
+                hwnd: hwnd as *mut _,
+            };
+            let win32_loader = khr::Win32Surface::new(entry, &self.raw.0);
+            unsafe { win32_loader.create_win32_surface(&info, None) }?
+        };
+
+        Ok(self.create_surface_from_vk_surface_khr(surface, width, height, 1))
     }
 
     #[cfg(target_os = "macos")]
     pub fn create_surface_from_nsview(&self, view: *mut c_void) -> Surface {
+        self.try_create_surface_from_nsview(view)
+            .expect("Unable to create macOS surface")
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn try_create_surface_from_nsview(
+        &self,
+        view: *mut c_void,
+    ) -> Result<Surface, SurfaceCreationError> {
         use ash::extensions::mvk;
         use core_graphics::{
             geometry::CGRect,
@@ -236,8 +438,16 @@ impl Instance {
         };
         use objc::runtime::{Object, YES, BOOL};
 
+        let entry = VK_ENTRY
+            .as_ref()
+            .expect("Unable to load Vulkan entry points");
+
+        if !self.extensions.contains(&mvk::MacOSSurface::name()) {
+            return Err(SurfaceCreationError::MissingExtension(mvk::MacOSSurface::name()));
+        }
+
         // TODO: this logic is duplicated from gfx-backend-metal, refactor?
-        unsafe {
+        let metal_layer = unsafe {
             let view = view as *mut Object;
             let existing: *mut Object = msg_send![view, layer];
             let class = class!(CAMetalLayer);
@@ -249,7 +459,10 @@ impl Instance {
                 result == YES
             };
 
-            if !use_current {
+            if use_current {
+                msg_send![existing, retain];
+                MetalLayer(ptr::NonNull::new_unchecked(existing))
+            } else {
                 let layer: *mut Object = msg_send![class, new];
                 msg_send![view, setLayer: layer];
                 let bounds: CGRect = msg_send![view, bounds];
@@ -260,16 +473,10 @@ impl Instance {
                     let scale_factor: CGFloat = msg_send![window, backingScaleFactor];
                     msg_send![layer, setContentsScale: scale_factor];
                 }
+                // `new` already returned a retained layer.
+                MetalLayer(ptr::NonNull::new_unchecked(layer))
             }
-        }
-
-        let entry = VK_ENTRY
-            .as_ref()
-            .expect("Unable to load Vulkan entry points");
-
-        if !self.extensions.contains(&mvk::MacOSSurface::name()) {
-            panic!("Vulkan driver does not support VK_MVK_MACOS_SURFACE");
-        }
+        };
 
         let surface = {
             let mac_os_loader = mvk::MacOSSurface::new(entry, &self.raw.0);
@@ -280,11 +487,7 @@ impl Instance {
                 p_view: view,
             };
 
-            unsafe {
-                mac_os_loader
-                    .create_mac_os_surface_mvk(&info, None)
-                    .expect("Unable to create macOS surface")
-            }
+            unsafe { mac_os_loader.create_mac_os_surface_mvk(&info, None) }?
         };
 
         let (width, height) = {
@@ -295,12 +498,27 @@ impl Instance {
             (bounds.size.width as u32, bounds.size.height as u32)
         };
 
-        self.create_surface_from_vk_surface_khr(surface, width, height, 1)
+        let mut surface = self.create_surface_from_vk_surface_khr(surface, width, height, 1);
+        surface.metal_layer = Some(metal_layer);
+        Ok(surface)
     }
 
     #[cfg(feature = "winit")]
-    #[allow(unreachable_code)]
     pub fn create_surface(&self, window: &winit::Window) -> Surface {
+        self.try_create_surface(window)
+            .expect("Unable to create a surface for the window")
+    }
+
+    /// Create a surface for a winit window.
+    ///
+    /// Fails with `SurfaceCreationError::NoSupportedWsi` if none of the window systems
+    /// supported by the platform are available, so applications can fall back to another backend.
+    #[cfg(feature = "winit")]
+    #[allow(unreachable_code)]
+    pub fn try_create_surface(
+        &self,
+        window: &winit::Window,
+    ) -> Result<Surface, SurfaceCreationError> {
         #[cfg(all(feature = "x11", unix, not(target_os = "android"), not(target_os = "macos")))]
         {
             use winit::os::unix::WindowExt;
@@ -310,7 +528,7 @@ impl Instance {
                     let display: *mut c_void = display as *mut _;
                     let surface: *mut c_void = window.get_wayland_surface().unwrap() as *mut _;
                     let px = window.get_inner_size().unwrap();
-                    return self.create_surface_from_wayland(
+                    return self.try_create_surface_from_wayland(
                         display,
                         surface,
                         px.width as _,
@@ -321,10 +539,10 @@ impl Instance {
             if self.extensions.contains(&khr::XlibSurface::name()) {
                 if let Some(display) = window.get_xlib_display() {
                     let window = window.get_xlib_window().unwrap();
-                    return self.create_surface_from_xlib(display as _, window);
+                    return self.try_create_surface_from_xlib(display as _, window);
                 }
             }
-            panic!("The Vulkan driver does not support surface creation!");
+            return Err(SurfaceCreationError::NoSupportedWsi);
         }
         #[cfg(target_os = "android")]
         {
@@ -332,7 +550,11 @@ impl Instance {
             let logical_size = window.get_inner_size().unwrap();
             let width = logical_size.width * window.get_hidpi_factor();
             let height = logical_size.height * window.get_hidpi_factor();
-            return self.create_surface_android(window.get_native_window(), width as _, height as _);
+            return self.try_create_surface_android(
+                window.get_native_window(),
+                width as _,
+                height as _,
+            );
         }
         #[cfg(windows)]
         {
@@ -341,16 +563,16 @@ impl Instance {
 
             let hinstance = unsafe { GetModuleHandleW(ptr::null()) };
             let hwnd = window.get_hwnd();
-            return self.create_surface_from_hwnd(hinstance as *mut _, hwnd as *mut _);
+            return self.try_create_surface_from_hwnd(hinstance as *mut _, hwnd as *mut _);
         }
         #[cfg(target_os = "macos")]
         {
             use winit::os::macos::WindowExt;
 
-            return self.create_surface_from_nsview(window.get_nsview());
+            return self.try_create_surface_from_nsview(window.get_nsview());
         }
         let _ = window;
-        panic!("No suitable WSI enabled!");
+        Err(SurfaceCreationError::NoSupportedWsi)
     }
 
     pub fn create_surface_from_vk_surface_khr(
@@ -360,47 +582,598 @@ impl Instance {
         height: Size,
         samples: NumSamples,
     ) -> Surface {
-        let entry = VK_ENTRY
-            .as_ref()
-            .expect("Unable to load Vulkan entry points");
+        self.wrap_vk_surface_khr(surface, width, height, samples, true)
+    }
 
-        let functor = khr::Surface::new(entry, &self.raw.0);
+    /// Wrap a surface without taking ownership of it.
+    ///
+    /// The surface will not be destroyed when the returned `Surface` is dropped,
+    /// which allows sharing it with the library which created it.
+    /// The caller must keep the surface alive for as long as the `Surface` is used.
+    pub fn create_surface_from_vk_surface_khr_borrowed(
+        &self,
+        surface: vk::SurfaceKHR,
+        width: Size,
+        height: Size,
+        samples: NumSamples,
+    ) -> Surface {
+        self.wrap_vk_surface_khr(surface, width, height, samples, false)
+    }
 
-        let raw = Arc::new(RawSurface {
-            handle: surface,
-            functor,
-            instance: self.raw.clone(),
-        });
+    fn wrap_vk_surface_khr(
+        &self,
+        surface: vk::SurfaceKHR,
+        width: Size,
+        height: Size,
+        samples: NumSamples,
+        owned: bool,
+    ) -> Surface {
+        let raw = Arc::new(RawSurface::new(surface, self.raw.clone(), owned));
 
         Surface {
             raw,
             width,
             height,
             samples,
+            swapchain_retries: 3,
+            pre_transform: None,
+            #[cfg(target_os = "macos")]
+            metal_layer: None,
         }
     }
 }
 
-impl hal::Surface<Backend> for Surface {
-    fn kind(&self) -> hal::image::Kind {
-        hal::image::Kind::D2(self.width, self.height, 1, self.samples)
+/// Availability of `VK_EXT_full_screen_exclusive` for a surface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FullScreenExclusiveSupport {
+    /// The extension is exposed by the physical device and exclusive fullscreen can be used.
+    Supported,
+    /// The extension is not available, applications should fall back to borderless windows.
+    Unsupported,
+}
+
+/// Likely cause of a `Suboptimal` result from acquiring or presenting an image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SuboptimalReason {
+    /// The surface extent no longer matches the swapchain extent.
+    Resized {
+        /// Current extent of the surface.
+        new_extent: hal::window::Extent2D,
+    },
+    /// No additional information is available.
+    Unknown,
+}
+
+/// Trade-off to optimize for when picking a present mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PresentProfile {
+    /// Minimize the input-to-display latency, at the cost of more GPU work.
+    LowLatency,
+    /// Minimize GPU wakeups, e.g. when running on battery.
+    PowerSaving,
+    /// Avoid tearing while keeping latency reasonable, using `Mailbox` when available.
+    Balanced,
+}
+
+/// How a swapchain reports images acquired while it's suboptimal for the surface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SuboptimalPolicy {
+    /// Return `Suboptimal` alongside the image and leave the decision to the application.
+    Report,
+    /// Return `Suboptimal` alongside the image, and fail the next acquisition
+    /// with `OutOfDate` so the swapchain gets recreated.
+    AlwaysRecreate,
+    /// Treat suboptimal images as optimal.
+    Ignore,
+    /// Hide `Suboptimal` from the current acquisition, and fail the next one
+    /// with `OutOfDate` so the swapchain gets recreated.
+    RecreateOnNextFrame,
+}
+
+impl Default for SuboptimalPolicy {
+    fn default() -> Self {
+        SuboptimalPolicy::Report
     }
+}
 
-    fn compatibility(
+/// A combination of swapchain parameters supported by a surface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SurfaceConfigOption {
+    /// Format of the swapchain images.
+    pub format: Format,
+    /// Color space the presentation engine interprets the images in.
+    pub color_space: vk::ColorSpaceKHR,
+    /// Presentation mode.
+    pub present_mode: hal::PresentMode,
+}
+
+/// Clamp `extent` into the range of extents supported by the surface.
+///
+/// Some drivers reject tiny extents (e.g. 1x1 for offscreen pacing), so the
+/// minimum supported extent is used instead.
+pub(crate) fn clamp_extent(
+    caps: &vk::SurfaceCapabilitiesKHR,
+    extent: hal::window::Extent2D,
+) -> hal::window::Extent2D {
+    hal::window::Extent2D {
+        width: extent
+            .width
+            .max(caps.min_image_extent.width)
+            .min(caps.max_image_extent.width),
+        height: extent
+            .height
+            .max(caps.min_image_extent.height)
+            .min(caps.max_image_extent.height),
+    }
+}
+
+impl Surface {
+    /// Get the instance this surface was created with.
+    ///
+    /// The surface keeps the instance alive, so subsystems holding only a surface
+    /// can keep using the instance.
+    pub fn instance(&self) -> Arc<RawInstance> {
+        self.raw.instance.clone()
+    }
+
+    /// Replace the native surface, e.g. after the window system recreated it for the same window.
+    ///
+    /// The new surface is created from the same instance and owned by this `Surface`.
+    /// The previous surface is released immediately, and destroyed if it was owned.
+    ///
+    /// # Safety
+    ///
+    /// All swapchains created for the previous surface must have been destroyed.
+    pub unsafe fn replace_raw(&mut self, surface: vk::SurfaceKHR) {
+        self.raw = Arc::new(RawSurface::new(surface, self.raw.instance.clone(), true));
+        #[cfg(target_os = "macos")]
+        {
+            self.metal_layer = None;
+        }
+    }
+
+    /// Set how many times swapchain creation is retried when the driver reports
+    /// that the surface changed in the meantime (e.g. during a window resize).
+    ///
+    /// The extent is re-clamped against fresh surface capabilities before each retry.
+    /// Defaults to 3.
+    pub fn set_swapchain_retries(&mut self, retries: u32) {
+        self.swapchain_retries = retries;
+    }
+
+    /// Override the transform new swapchains are created with.
+    ///
+    /// By default (`None`), swapchains use the `current_transform` of the surface,
+    /// which requires the application to pre-rotate its rendering. Pass `IDENTITY`
+    /// to let the compositor rotate the content instead. Transforms missing from
+    /// `supported_transforms` fall back to the current transform.
+    pub fn set_pre_transform(&mut self, transform: Option<vk::SurfaceTransformFlagsKHR>) {
+        self.pre_transform = transform;
+    }
+
+    /// Get the numeric value of the native `VkSurfaceKHR` handle,
+    /// for correlating with validation layer messages.
+    pub fn raw_handle(&self) -> u64 {
+        use ash::vk::Handle;
+        self.raw.handle.as_raw()
+    }
+
+    /// Pick a swapchain format which can be written by a compute shader as a storage image.
+    ///
+    /// sRGB formats can't be used as storage images on most implementations, so only
+    /// linear (UNORM/FLOAT) formats are considered. The application is responsible for
+    /// applying gamma correction manually when writing into such an image.
+    ///
+    /// Returns `None` if the surface doesn't support `STORAGE` usage or none of the
+    /// supported formats can be used as a storage image.
+    pub fn preferred_storage_format(&self, physical_device: &PhysicalDevice) -> Option<Format> {
+        use crate::hal::format::{ChannelType, ImageFeature};
+
+        let (caps, formats, _) = hal::Surface::compatibility(self, physical_device);
+        if !caps.usage.contains(hal::image::Usage::STORAGE) {
+            return None;
+        }
+
+        // If the surface has no preferred format, fall back to the common linear ones.
+        let formats = formats.unwrap_or_else(|| {
+            vec![
+                Format::Bgra8Unorm,
+                Format::Rgba8Unorm,
+                Format::A2b10g10r10Unorm,
+                Format::Rgba16Sfloat,
+            ]
+        });
+
+        formats.into_iter().find(|&format| {
+            match format.base_format().1 {
+                ChannelType::Unorm | ChannelType::Ufloat | ChannelType::Sfloat => {}
+                _ => return false,
+            }
+            hal::PhysicalDevice::format_properties(physical_device, Some(format))
+                .optimal_tiling
+                .contains(ImageFeature::STORAGE)
+        })
+    }
+
+    /// Check if exclusive fullscreen via `VK_EXT_full_screen_exclusive` can be used.
+    ///
+    /// Older drivers (notably NVIDIA ones) don't expose the extension and reject
+    /// exclusive fullscreen requests, in which case borderless windowed mode should be used.
+    pub fn fullscreen_exclusive_support(
         &self,
         physical_device: &PhysicalDevice,
-    ) -> (
-        hal::SurfaceCapabilities,
-        Option<Vec<Format>>,
-        Vec<hal::PresentMode>,
-    ) {
-        // Capabilities
-        let caps = unsafe {
+    ) -> FullScreenExclusiveSupport {
+        let name = CStr::from_bytes_with_nul(b"VK_EXT_full_screen_exclusive\0").unwrap();
+        if physical_device.supports_extension(name) {
+            FullScreenExclusiveSupport::Supported
+        } else {
+            FullScreenExclusiveSupport::Unsupported
+        }
+    }
+
+    /// Find out why `swapchain` was reported as suboptimal for this surface.
+    ///
+    /// A resize is benign and the swapchain can still be used until it's recreated.
+    pub fn suboptimal_reason(
+        &self,
+        physical_device: &PhysicalDevice,
+        swapchain: &Swapchain,
+    ) -> SuboptimalReason {
+        let (caps, _, _) = hal::Surface::compatibility(self, physical_device);
+        match caps.current_extent {
+            Some(new_extent) if new_extent != swapchain.config.extent => {
+                SuboptimalReason::Resized { new_extent }
+            }
+            _ => SuboptimalReason::Unknown,
+        }
+    }
+
+    /// Query the present modes supported by this physical device.
+    ///
+    /// Support can change when the window moves to another monitor,
+    /// so this is cheaper than a full `compatibility` query when re-validating.
+    pub fn present_modes(&self, physical_device: &PhysicalDevice) -> Vec<hal::PresentMode> {
+        let present_modes = unsafe {
             self.raw
                 .functor
-                .get_physical_device_surface_capabilities(physical_device.handle, self.raw.handle)
+                .get_physical_device_surface_present_modes(physical_device.handle, self.raw.handle)
         }
-        .expect("Unable to query surface capabilities");
+        .expect("Unable to query present modes");
+        present_modes
+            .into_iter()
+            .filter_map(conv::map_vk_present_mode)
+            .collect()
+    }
+
+    /// Enumerate the physical devices of the surface's instance that have at least one
+    /// queue family able to present to it.
+    ///
+    /// Devices can appear or disappear at runtime (e.g. an external GPU being connected),
+    /// so this is re-evaluated on every call. Returns an empty list if enumeration fails.
+    pub fn presentable_physical_devices(&self) -> Vec<PhysicalDevice> {
+        let instance = &self.raw.instance;
+        let devices = match unsafe { instance.0.enumerate_physical_devices() } {
+            Ok(devices) => devices,
+            Err(err) => {
+                error!("Unable to enumerate physical devices: {:?}", err);
+                return Vec::new();
+            }
+        };
+        devices
+            .into_iter()
+            .map(|handle| PhysicalDevice {
+                instance: instance.clone(),
+                handle,
+                properties: unsafe { instance.0.get_physical_device_properties(handle) },
+            })
+            .filter(|physical_device| {
+                physical_device
+                    .present_support(self)
+                    .iter()
+                    .any(|&(_, supported)| supported)
+            })
+            .collect()
+    }
+
+    /// Query present support for every queue family of each of the given physical devices.
+    ///
+    /// The result has one row per physical device, indexed by queue family.
+    pub fn present_support_matrix(&self, physical_devices: &[PhysicalDevice]) -> Vec<Vec<bool>> {
+        physical_devices
+            .iter()
+            .map(|physical_device| {
+                physical_device
+                    .present_support(self)
+                    .into_iter()
+                    .map(|(_, supported)| supported)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Check if swapchain images need a queue family ownership transfer before presentation.
+    ///
+    /// Swapchain images are created with exclusive sharing, so this is the case whenever
+    /// rendering and presentation happen on different queue families.
+    pub fn requires_ownership_transfer(
+        &self,
+        graphics_family: &QueueFamily,
+        present_family: &QueueFamily,
+    ) -> bool {
+        graphics_family.index != present_family.index
+    }
+
+    /// Check whether the surface currently dictates the swapchain extent.
+    ///
+    /// Some compositors switch between a fixed extent and letting the application
+    /// choose during mode changes, so this shouldn't be cached. When it returns `false`,
+    /// the application is expected to track the window size itself.
+    pub fn extent_is_surface_driven(&self, physical_device: &PhysicalDevice) -> bool {
+        self.capabilities_raw(physical_device.handle).map_or(false, |caps| {
+            caps.current_extent.width != !0 && caps.current_extent.height != !0
+        })
+    }
+
+    /// Get the `CAMetalLayer` backing this surface, for interop with native Metal code.
+    ///
+    /// Returns `None` if the surface wasn't created from an `NSView`.
+    #[cfg(target_os = "macos")]
+    pub fn metal_layer(&self) -> Option<*mut c_void> {
+        self.metal_layer
+            .as_ref()
+            .map(|layer| layer.0.as_ptr() as *mut c_void)
+    }
+
+    /// Update the backing scale of the `CAMetalLayer` after the window moved to a display
+    /// with a different DPI, so the next swapchain is created with the right size.
+    #[cfg(target_os = "macos")]
+    pub fn update_scale_factor(&mut self, scale: f64) {
+        use core_graphics::{
+            base::CGFloat,
+            geometry::{CGRect, CGSize},
+        };
+
+        let layer = match self.metal_layer {
+            Some(ref layer) => layer.0.as_ptr(),
+            None => {
+                warn!("Surface was not created from an NSView, unable to update the scale");
+                return;
+            }
+        };
+
+        unsafe {
+            let bounds: CGRect = msg_send![layer, bounds];
+            let drawable_size = CGSize::new(
+                bounds.size.width * scale as CGFloat,
+                bounds.size.height * scale as CGFloat,
+            );
+            msg_send![layer, setContentsScale: scale as CGFloat];
+            msg_send![layer, setDrawableSize: drawable_size];
+
+            self.width = drawable_size.width as Size;
+            self.height = drawable_size.height as Size;
+        }
+    }
+
+    /// Human-readable summary of the surface support on this physical device,
+    /// useful for logging driver differences.
+    pub fn summary(&self, physical_device: &PhysicalDevice) -> String {
+        let (caps, formats, present_modes) = hal::Surface::compatibility(self, physical_device);
+        let formats = match formats {
+            Some(formats) => format!("{} formats", formats.len()),
+            None => "any format".to_string(),
+        };
+        format!("{}, {}, present modes {:?}", caps, formats, present_modes)
+    }
+
+    /// Query the current transform of the surface relative to the presentation engine's
+    /// natural orientation.
+    ///
+    /// Swapchains are created with this transform, unless overridden with `set_pre_transform`,
+    /// so the application is expected to pre-rotate its rendering (e.g. in the projection
+    /// matrix) accordingly.
+    pub fn current_transform(
+        &self,
+        physical_device: &PhysicalDevice,
+    ) -> vk::SurfaceTransformFlagsKHR {
+        self.capabilities_raw(physical_device.handle)
+            .map_or(vk::SurfaceTransformFlagsKHR::IDENTITY, |caps| caps.current_transform)
+    }
+
+    /// Get the column-major clip-space matrix pre-rotating rendering for `transform`,
+    /// to be applied after the projection matrix.
+    ///
+    /// Rotations are clockwise on screen, mirrored variants flip horizontally before
+    /// rotating. `INHERIT` and unknown transforms map to the identity.
+    pub fn transform_matrix(transform: vk::SurfaceTransformFlagsKHR) -> [[f32; 4]; 4] {
+        use ash::vk::SurfaceTransformFlagsKHR as T;
+
+        // Maps (x, y) to (a * x + b * y, c * x + d * y).
+        let (a, b, c, d) = match transform {
+            T::ROTATE_90 => (0.0, -1.0, 1.0, 0.0),
+            T::ROTATE_180 => (-1.0, 0.0, 0.0, -1.0),
+            T::ROTATE_270 => (0.0, 1.0, -1.0, 0.0),
+            T::HORIZONTAL_MIRROR => (-1.0, 0.0, 0.0, 1.0),
+            T::HORIZONTAL_MIRROR_ROTATE_90 => (0.0, -1.0, -1.0, 0.0),
+            T::HORIZONTAL_MIRROR_ROTATE_180 => (1.0, 0.0, 0.0, -1.0),
+            T::HORIZONTAL_MIRROR_ROTATE_270 => (0.0, 1.0, 1.0, 0.0),
+            _ => (1.0, 0.0, 0.0, 1.0),
+        };
+        [
+            [a, c, 0.0, 0.0],
+            [b, d, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+    }
+
+    /// Query the transforms the presentation engine can apply to this surface.
+    ///
+    /// If the current transform isn't supported as a swapchain `pre_transform`, the
+    /// compositor rotates the content instead of the application pre-rotating it.
+    pub fn supported_transforms(
+        &self,
+        physical_device: &PhysicalDevice,
+    ) -> vk::SurfaceTransformFlagsKHR {
+        self.capabilities_raw(physical_device.handle)
+            .map_or(vk::SurfaceTransformFlagsKHR::IDENTITY, |caps| caps.supported_transforms)
+    }
+
+    /// Query the raw surface capabilities, logging any failure.
+    pub(crate) fn capabilities_raw(
+        &self,
+        physical_device: vk::PhysicalDevice,
+    ) -> Result<vk::SurfaceCapabilitiesKHR, vk::Result> {
+        unsafe {
+            self.raw
+                .functor
+                .get_physical_device_surface_capabilities(physical_device, self.raw.handle)
+        }
+        .map_err(|err| {
+            error!("Unable to query surface capabilities: {:?}", err);
+            err
+        })
+    }
+
+    /// Enumerate every valid combination of format, color space and present mode
+    /// supported by the surface on this physical device.
+    ///
+    /// Unlike `compatibility`, this keeps the pairing of formats with their color spaces.
+    /// If the surface has no preferred format, the result is empty.
+    pub fn enumerate_configs(&self, physical_device: &PhysicalDevice) -> Vec<SurfaceConfigOption> {
+        let formats = unsafe {
+            self.raw
+                .functor
+                .get_physical_device_surface_formats(physical_device.handle, self.raw.handle)
+        }
+        .expect("Unable to query surface formats");
+        let present_modes = self.present_modes(physical_device);
+
+        let mut configs = Vec::new();
+        for sf in formats {
+            let format = match conv::map_vk_format(sf.format) {
+                Some(format) => format,
+                None => continue,
+            };
+            for &present_mode in &present_modes {
+                configs.push(SurfaceConfigOption {
+                    format,
+                    color_space: sf.color_space,
+                    present_mode,
+                });
+            }
+        }
+        configs
+    }
+
+    /// Pick the most appropriate supported present mode for the given profile.
+    ///
+    /// `Fifo` is always supported and is used for `PowerSaving`, as well as
+    /// the fallback for other profiles.
+    pub fn choose_present_mode_for(
+        &self,
+        physical_device: &PhysicalDevice,
+        profile: PresentProfile,
+    ) -> hal::PresentMode {
+        use crate::hal::PresentMode as Pm;
+
+        let preferences: &[Pm] = match profile {
+            PresentProfile::LowLatency => &[Pm::Mailbox, Pm::Immediate, Pm::Relaxed],
+            PresentProfile::PowerSaving => &[],
+            PresentProfile::Balanced => &[Pm::Mailbox],
+        };
+        let present_modes = self.present_modes(physical_device);
+        preferences
+            .iter()
+            .cloned()
+            .find(|mode| present_modes.contains(mode))
+            .unwrap_or(Pm::Fifo)
+    }
+
+    /// Switch the present mode of `swapchain`, keeping the rest of its configuration.
+    ///
+    /// The swapchain is recreated in place, passing the current one as `old_swapchain`.
+    /// Returns the new backbuffer images, or `None` if nothing was recreated because
+    /// the swapchain already uses `mode`. Fails with `UnsupportedPresentMode` if the
+    /// surface doesn't support `mode`, leaving the swapchain untouched.
+    ///
+    /// If the recreation itself fails, the old swapchain has already been destroyed:
+    /// acquiring from `swapchain` then fails with `OutOfDate`, and it has to be
+    /// recreated without passing it as `old_swapchain`.
+    pub unsafe fn set_present_mode(
+        &mut self,
+        physical_device: &PhysicalDevice,
+        swapchain: &mut Swapchain,
+        mode: hal::PresentMode,
+    ) -> Result<Option<Vec<native::Image>>, hal::window::CreationError> {
+        if swapchain.config.present_mode == mode {
+            return Ok(None);
+        }
+
+        if !self.present_modes(physical_device).contains(&mode) {
+            return Err(hal::window::CreationError::UnsupportedPresentMode(mode));
+        }
+
+        let old_swapchain = Swapchain {
+            raw: swapchain.raw,
+            functor: swapchain.functor.clone(),
+            config: swapchain.config.clone(),
+            device: swapchain.device.clone(),
+            suboptimal_policy: swapchain.suboptimal_policy,
+            recreate_pending: false,
+            default_acquire_timeout: swapchain.default_acquire_timeout,
+        };
+        let device = Device {
+            raw: swapchain.device.clone(),
+        };
+        let config = swapchain.config.clone().with_mode(mode);
+        let (mut new_swapchain, images) =
+            match hal::Device::create_swapchain(&device, self, config, Some(old_swapchain)) {
+                Ok(result) => result,
+                Err(err) => {
+                    // The old handle was destroyed by `create_swapchain`.
+                    swapchain.raw = vk::SwapchainKHR::null();
+                    return Err(err);
+                }
+            };
+        new_swapchain.suboptimal_policy = swapchain.suboptimal_policy;
+        new_swapchain.default_acquire_timeout = swapchain.default_acquire_timeout;
+        *swapchain = new_swapchain;
+
+        Ok(Some(images))
+    }
+
+    /// Create a swapchain suited for benchmarking, with uncapped presentation.
+    ///
+    /// Uses `Immediate` if supported, falling back to `Mailbox` and then `Fifo`,
+    /// and the minimum number of images supported by the surface.
+    /// Returns the present mode that was actually chosen along with the swapchain.
+    pub unsafe fn create_benchmark_swapchain(
+        &mut self,
+        device: &Device,
+        physical_device: &PhysicalDevice,
+        format: Format,
+        extent: hal::window::Extent2D,
+    ) -> Result<(Swapchain, Vec<native::Image>, hal::PresentMode), hal::window::CreationError> {
+        use crate::hal::PresentMode as Pm;
+
+        let (caps, _, present_modes) = hal::Surface::compatibility(self, physical_device);
+        let mode = [Pm::Immediate, Pm::Mailbox]
+            .iter()
+            .cloned()
+            .find(|mode| present_modes.contains(mode))
+            .unwrap_or(Pm::Fifo);
+
+        let mut config = hal::SwapchainConfig::from_caps(&caps, format, extent).with_mode(mode);
+        config.image_count = caps.image_count.start;
+        let (swapchain, images) = hal::Device::create_swapchain(device, self, config, None)?;
+        Ok((swapchain, images, mode))
+    }
+
+    fn capabilities(&self, physical_device: &PhysicalDevice) -> hal::SurfaceCapabilities {
+        let caps = self
+            .capabilities_raw(physical_device.handle)
+            .expect("Unable to query surface capabilities");
 
         // If image count is 0, the support number of images is unlimited.
         let max_images = if caps.max_image_count == 0 {
@@ -430,14 +1203,114 @@ impl hal::Surface<Backend> for Surface {
             height: caps.max_image_extent.height,
         };
 
-        let capabilities = hal::SurfaceCapabilities {
+        hal::SurfaceCapabilities {
             image_count: caps.min_image_count..max_images,
             current_extent,
             extents: min_extent..max_extent,
             max_image_layers: caps.max_image_array_layers as _,
             usage: conv::map_vk_image_usage(caps.supported_usage_flags),
             composite_alpha: conv::map_vk_composite_alpha(caps.supported_composite_alpha),
-        };
+        }
+    }
+
+    /// Query the surface compatibility like `compatibility`, reusing the given buffers
+    /// for the formats and present modes.
+    ///
+    /// The driver is queried through small on-stack buffers, so polling capabilities
+    /// doesn't allocate once the buffers have grown large enough. `formats` is left
+    /// empty if the surface has no preferred format and any format can be used.
+    pub fn compatibility_into(
+        &self,
+        physical_device: &PhysicalDevice,
+        formats: &mut Vec<Format>,
+        present_modes: &mut Vec<hal::PresentMode>,
+    ) -> hal::SurfaceCapabilities {
+        let fp = &self.raw.surface_fn;
+        formats.clear();
+        present_modes.clear();
+
+        let mut raw_formats = SmallVec::<[vk::SurfaceFormatKHR; 16]>::new();
+        let mut count = 0;
+        unsafe {
+            let result = fp.get_physical_device_surface_formats_khr(
+                physical_device.handle,
+                self.raw.handle,
+                &mut count,
+                ptr::null_mut(),
+            );
+            assert_eq!(result, vk::Result::SUCCESS, "Unable to query surface formats");
+            raw_formats.reserve(count as usize);
+            let result = fp.get_physical_device_surface_formats_khr(
+                physical_device.handle,
+                self.raw.handle,
+                &mut count,
+                raw_formats.as_mut_ptr(),
+            );
+            // The list may have shrunk or grown in between, keep what was returned.
+            assert!(
+                result == vk::Result::SUCCESS || result == vk::Result::INCOMPLETE,
+                "Unable to query surface formats"
+            );
+            raw_formats.set_len(count as usize);
+        }
+        match raw_formats.first() {
+            Some(sf) if sf.format == vk::Format::UNDEFINED => {}
+            _ => formats.extend(
+                raw_formats
+                    .iter()
+                    .filter_map(|sf| conv::map_vk_format(sf.format)),
+            ),
+        }
+
+        let mut raw_present_modes = SmallVec::<[vk::PresentModeKHR; 8]>::new();
+        let mut count = 0;
+        unsafe {
+            let result = fp.get_physical_device_surface_present_modes_khr(
+                physical_device.handle,
+                self.raw.handle,
+                &mut count,
+                ptr::null_mut(),
+            );
+            assert_eq!(result, vk::Result::SUCCESS, "Unable to query present modes");
+            raw_present_modes.reserve(count as usize);
+            let result = fp.get_physical_device_surface_present_modes_khr(
+                physical_device.handle,
+                self.raw.handle,
+                &mut count,
+                raw_present_modes.as_mut_ptr(),
+            );
+            // The list may have shrunk or grown in between, keep what was returned.
+            assert!(
+                result == vk::Result::SUCCESS || result == vk::Result::INCOMPLETE,
+                "Unable to query present modes"
+            );
+            raw_present_modes.set_len(count as usize);
+        }
+        present_modes.extend(
+            raw_present_modes
+                .iter()
+                .cloned()
+                .filter_map(conv::map_vk_present_mode),
+        );
+
+        self.capabilities(physical_device)
+    }
+}
+
+impl hal::Surface<Backend> for Surface {
+    fn kind(&self) -> hal::image::Kind {
+        hal::image::Kind::D2(self.width, self.height, 1, self.samples)
+    }
+
+    fn compatibility(
+        &self,
+        physical_device: &PhysicalDevice,
+    ) -> (
+        hal::SurfaceCapabilities,
+        Option<Vec<Format>>,
+        Vec<hal::PresentMode>,
+    ) {
+        let capabilities = self.capabilities(physical_device);
 
         // Swapchain formats
         let formats = unsafe {
@@ -460,16 +1333,7 @@ impl hal::Surface<Backend> for Surface {
             ),
         };
 
-        let present_modes = unsafe {
-            self.raw
-                .functor
-                .get_physical_device_surface_present_modes(physical_device.handle, self.raw.handle)
-        }
-        .expect("Unable to query present modes");
-        let present_modes = present_modes
-            .into_iter()
-            .map(conv::map_vk_present_mode)
-            .collect();
+        let present_modes = self.present_modes(physical_device);
 
         (capabilities, formats, present_modes)
     }
@@ -491,6 +1355,368 @@ pub struct Swapchain {
     pub(crate) raw: vk::SwapchainKHR,
     #[derivative(Debug = "ignore")]
     pub(crate) functor: khr::Swapchain,
+    pub(crate) config: hal::SwapchainConfig,
+    pub(crate) device: Arc<RawDevice>,
+    pub(crate) suboptimal_policy: SuboptimalPolicy,
+    pub(crate) recreate_pending: bool,
+    pub(crate) default_acquire_timeout: u64,
+}
+
+impl Swapchain {
+    /// Set how images acquired while the swapchain is suboptimal are reported.
+    pub fn set_suboptimal_policy(&mut self, policy: SuboptimalPolicy) {
+        self.suboptimal_policy = policy;
+    }
+
+    /// Get the swapchain extension functions and the native swapchain handle,
+    /// for calling into extensions not wrapped by the backend.
+    ///
+    /// # Safety
+    ///
+    /// The handle is owned by this `Swapchain` and is only valid while it's alive.
+    /// It must not be destroyed or retired (e.g. passed as `oldSwapchain`) through
+    /// the returned functions, and any state changed through them isn't tracked.
+    pub unsafe fn raw_parts(&self) -> (&khr::Swapchain, vk::SwapchainKHR) {
+        (&self.functor, self.raw)
+    }
+
+    /// Check that the swapchain was created with the `expected` extent, e.g. the one
+    /// used for the framebuffers rendered into its images.
+    ///
+    /// Meant to be called in debug builds before rendering, to catch resize handling bugs.
+    pub fn assert_extent(&self, expected: hal::window::Extent2D) -> Result<(), ExtentMismatch> {
+        if self.config.extent == expected {
+            Ok(())
+        } else {
+            Err(ExtentMismatch {
+                expected,
+                actual: self.config.extent,
+            })
+        }
+    }
+
+    /// Create a multisampled color target matching the swapchain images, to be used as
+    /// a transient attachment resolved into them.
+    ///
+    /// The image is created with `TRANSIENT_ATTACHMENT` usage and bound to memory with
+    /// `Properties::LAZILY_ALLOCATED` where available, which avoids allocating the samples
+    /// on tile-based GPUs, falling back to `Properties::DEVICE_LOCAL` memory. Fails with
+    /// `CreationError::Samples` if the sample count isn't supported for color attachments.
+    ///
+    /// The image, its memory and its view are owned by the caller and have to be destroyed
+    /// after the last frame using them.
+    pub unsafe fn create_transient_msaa_target(
+        &self,
+        physical_device: &PhysicalDevice,
+        samples: NumSamples,
+    ) -> Result<(native::Image, native::Memory, native::ImageView), TransientTargetError> {
+        use crate::hal::format::Swizzle;
+        use crate::hal::image as i;
+        use crate::hal::memory::Properties;
+        use crate::hal::MemoryTypeId;
+
+        let limits = hal::PhysicalDevice::limits(physical_device);
+        if !samples.is_power_of_two() || limits.framebuffer_color_samples_count & samples == 0 {
+            return Err(TransientTargetError::Image(i::CreationError::Samples(samples)));
+        }
+        let device = Device {
+            raw: self.device.clone(),
+        };
+        let mut image = hal::Device::create_image(
+            &device,
+            i::Kind::D2(
+                self.config.extent.width,
+                self.config.extent.height,
+                self.config.image_layers,
+                samples,
+            ),
+            1,
+            self.config.format,
+            i::Tiling::Optimal,
+            i::Usage::COLOR_ATTACHMENT | i::Usage::TRANSIENT_ATTACHMENT,
+            i::ViewCapabilities::empty(),
+        )
+        .map_err(TransientTargetError::Image)?;
+
+        let requirements = hal::Device::get_image_requirements(&device, &image);
+        let memory_types = hal::PhysicalDevice::memory_properties(physical_device).memory_types;
+        let find_type = |properties: Properties| {
+            memory_types
+                .iter()
+                .enumerate()
+                .position(|(id, ty)| {
+                    requirements.type_mask & (1 << id) != 0 && ty.properties.contains(properties)
+                })
+                .map(MemoryTypeId)
+        };
+        let memory_type = match find_type(Properties::DEVICE_LOCAL | Properties::LAZILY_ALLOCATED)
+            .or_else(|| find_type(Properties::DEVICE_LOCAL))
+        {
+            Some(memory_type) => memory_type,
+            None => {
+                hal::Device::destroy_image(&device, image);
+                return Err(TransientTargetError::NoMemoryType);
+            }
+        };
+
+        let memory = match hal::Device::allocate_memory(&device, memory_type, requirements.size) {
+            Ok(memory) => memory,
+            Err(err) => {
+                hal::Device::destroy_image(&device, image);
+                return Err(TransientTargetError::Allocation(err));
+            }
+        };
+        if let Err(err) = hal::Device::bind_image_memory(&device, &memory, 0, &mut image) {
+            hal::Device::destroy_image(&device, image);
+            hal::Device::free_memory(&device, memory);
+            return Err(TransientTargetError::Bind(err));
+        }
+
+        let view_kind = if self.config.image_layers > 1 {
+            i::ViewKind::D2Array
+        } else {
+            i::ViewKind::D2
+        };
+        let view = hal::Device::create_image_view(
+            &device,
+            &image,
+            view_kind,
+            self.config.format,
+            Swizzle::NO,
+            i::SubresourceRange {
+                aspects: conv::format_aspects(self.config.format),
+                levels: 0..1,
+                layers: 0..self.config.image_layers,
+            },
+        );
+        match view {
+            Ok(view) => Ok((image, memory, view)),
+            Err(err) => {
+                hal::Device::destroy_image(&device, image);
+                hal::Device::free_memory(&device, memory);
+                Err(TransientTargetError::View(err))
+            }
+        }
+    }
+
+    /// Build the barrier transferring a rendered swapchain image, already in the `Present`
+    /// layout, from the graphics queue family to the present queue family.
+    ///
+    /// The same barrier has to be recorded on both queues: as a release after rendering
+    /// and as an acquire before presenting. Returns `None` if no transfer is required,
+    /// see `Surface::requires_ownership_transfer`.
+    pub fn present_ownership_barrier<'a>(
+        &self,
+        image: &'a native::Image,
+        graphics_family: &QueueFamily,
+        present_family: &QueueFamily,
+    ) -> Option<hal::memory::Barrier<'a, Backend>> {
+        use crate::hal::image::{Access, Layout, SubresourceRange};
+
+        if graphics_family.index == present_family.index {
+            return None;
+        }
+        let families = hal::queue::QueueFamily::id(graphics_family)
+            ..hal::queue::QueueFamily::id(present_family);
+        Some(hal::memory::Barrier::Image {
+            states: (Access::COLOR_ATTACHMENT_WRITE, Layout::Present)
+                ..(Access::empty(), Layout::Present),
+            target: image,
+            families: Some(families),
+            range: SubresourceRange {
+                aspects: conv::format_aspects(self.config.format),
+                levels: 0..1,
+                layers: 0..self.config.image_layers,
+            },
+        })
+    }
+
+    /// Record a clear of the acquired swapchain `image` to `color`, leaving it ready
+    /// for presentation, without the need for a render pass.
+    ///
+    /// The swapchain must have been created with `TRANSFER_DST` usage. The previous
+    /// contents of the image are discarded.
+    pub unsafe fn clear_image(
+        &self,
+        command_buffer: &mut CommandBuffer,
+        image: &native::Image,
+        color: hal::command::ClearColor,
+    ) {
+        use crate::hal::command::{ClearDepthStencilRaw, RawCommandBuffer};
+        use crate::hal::image::{Access, Layout, SubresourceRange, Usage};
+        use crate::hal::memory::{Barrier, Dependencies};
+        use crate::hal::pso::PipelineStage;
+
+        assert!(
+            self.config.image_usage.contains(Usage::TRANSFER_DST),
+            "Swapchain images must have TRANSFER_DST usage to be cleared"
+        );
+
+        let range = SubresourceRange {
+            aspects: conv::format_aspects(self.config.format),
+            levels: 0..1,
+            layers: 0..self.config.image_layers,
+        };
+        let to_transfer = Barrier::Image {
+            states: (Access::empty(), Layout::Undefined)
+                ..(Access::TRANSFER_WRITE, Layout::TransferDstOptimal),
+            target: image,
+            families: None,
+            range: range.clone(),
+        };
+        command_buffer.pipeline_barrier(
+            PipelineStage::TOP_OF_PIPE..PipelineStage::TRANSFER,
+            Dependencies::empty(),
+            iter::once(&to_transfer),
+        );
+        command_buffer.clear_image(
+            image,
+            Layout::TransferDstOptimal,
+            color.into(),
+            ClearDepthStencilRaw {
+                depth: 0.0,
+                stencil: 0,
+            },
+            iter::once(&range),
+        );
+        let to_present = Barrier::Image {
+            states: (Access::TRANSFER_WRITE, Layout::TransferDstOptimal)
+                ..(Access::empty(), Layout::Present),
+            target: image,
+            families: None,
+            range,
+        };
+        command_buffer.pipeline_barrier(
+            PipelineStage::TRANSFER..PipelineStage::BOTTOM_OF_PIPE,
+            Dependencies::empty(),
+            iter::once(&to_present),
+        );
+    }
+
+    /// Get the format of the swapchain images, for creating matching render passes.
+    pub fn format(&self) -> Format {
+        self.config.format
+    }
+
+    /// Get the extent of the swapchain images, for sizing framebuffers.
+    ///
+    /// This may differ from the requested extent, which is clamped into the range
+    /// supported by the surface, e.g. when a 1x1 swapchain is requested.
+    pub fn extent(&self) -> hal::window::Extent2D {
+        self.config.extent
+    }
+
+    /// Get the present mode the swapchain was created with.
+    ///
+    /// This may differ from the mode originally requested by the application if a
+    /// fallback was chosen, e.g. by `Surface::create_benchmark_swapchain`.
+    pub fn present_mode(&self) -> hal::PresentMode {
+        self.config.present_mode
+    }
+
+    /// Estimate the memory consumed by the swapchain images, in bytes.
+    ///
+    /// This is based on the requested image count, which the driver may exceed,
+    /// and doesn't account for any padding or compression applied by the driver.
+    pub fn memory_bytes(&self) -> u64 {
+        let format = self.config.format;
+        let extent = self.config.extent;
+        let image_bytes = match conv::format_bytes_per_pixel(format) {
+            Some(bytes) => extent.width as u64 * extent.height as u64 * bytes as u64,
+            None => {
+                let (block_width, block_height) = conv::block_dimensions(format);
+                let blocks_x = (extent.width + block_width - 1) / block_width;
+                let blocks_y = (extent.height + block_height - 1) / block_height;
+                blocks_x as u64 * blocks_y as u64 * (format.surface_desc().bits / 8) as u64
+            }
+        };
+        self.config.image_count as u64 * self.config.image_layers as u64 * image_bytes
+    }
+
+    /// Set the timeout used by `acquire_image_default`, in nanoseconds.
+    ///
+    /// Defaults to `!0`, which waits indefinitely.
+    pub fn set_default_acquire_timeout(&mut self, timeout_ns: u64) {
+        self.default_acquire_timeout = timeout_ns;
+    }
+
+    /// Acquire the next image, waiting for up to the default acquire timeout.
+    pub unsafe fn acquire_image_default(
+        &mut self,
+        semaphore: Option<&native::Semaphore>,
+        fence: Option<&native::Fence>,
+    ) -> Result<(hal::SwapImageIndex, Option<hal::window::Suboptimal>), hal::AcquireError> {
+        let timeout_ns = self.default_acquire_timeout;
+        hal::Swapchain::acquire_image(self, timeout_ns, semaphore, fence)
+    }
+
+    /// Acquire the next image, blocking until one is available.
+    pub unsafe fn acquire_image_blocking(
+        &mut self,
+        semaphore: Option<&native::Semaphore>,
+        fence: Option<&native::Fence>,
+    ) -> Result<(hal::SwapImageIndex, Option<hal::window::Suboptimal>), hal::AcquireError> {
+        hal::Swapchain::acquire_image(self, !0, semaphore, fence)
+    }
+
+    /// Acquire the next image, waiting in short slices so the wait can be
+    /// cancelled from another thread by setting `cancel`.
+    ///
+    /// Returns `CancellableAcquireError::Cancelled` if `cancel` is set before an image
+    /// is ready.
+    pub unsafe fn acquire_image_cancellable(
+        &mut self,
+        timeout_ns: u64,
+        cancel: &AtomicBool,
+        semaphore: Option<&native::Semaphore>,
+        fence: Option<&native::Fence>,
+    ) -> Result<(hal::SwapImageIndex, Option<hal::window::Suboptimal>), CancellableAcquireError>
+    {
+        const SLICE_NS: u64 = 1_000_000;
+
+        let mut remaining = timeout_ns;
+        loop {
+            if cancel.load(Ordering::Acquire) {
+                return Err(CancellableAcquireError::Cancelled);
+            }
+            let slice = remaining.min(SLICE_NS);
+            match hal::Swapchain::acquire_image(self, slice, semaphore, fence) {
+                Err(hal::AcquireError::Timeout) if remaining > slice => {
+                    // An infinite timeout never runs out.
+                    if timeout_ns != !0 {
+                        remaining -= slice;
+                    }
+                }
+                result => return result.map_err(CancellableAcquireError::Acquire),
+            }
+        }
+    }
+
+    /// Present `image_index` and immediately acquire the next image.
+    ///
+    /// Presentation errors, such as `OutOfDate`, are returned before attempting
+    /// to acquire, so the caller can recreate the swapchain. The result is suboptimal
+    /// if either the presentation or the acquisition was.
+    pub unsafe fn present_and_acquire(
+        &mut self,
+        queue: &mut CommandQueue,
+        image_index: hal::SwapImageIndex,
+        wait_semaphores: &[&native::Semaphore],
+        timeout_ns: u64,
+        semaphore: Option<&native::Semaphore>,
+        fence: Option<&native::Fence>,
+    ) -> Result<(hal::SwapImageIndex, Option<hal::window::Suboptimal>), PresentAndAcquireError> {
+        let present_suboptimal = hal::queue::RawCommandQueue::present(
+            queue,
+            iter::once((&*self, image_index)),
+            wait_semaphores.iter().cloned(),
+        )
+        .map_err(PresentAndAcquireError::Present)?;
+        let (index, acquire_suboptimal) =
+            hal::Swapchain::acquire_image(self, timeout_ns, semaphore, fence)
+                .map_err(PresentAndAcquireError::Acquire)?;
+        Ok((index, acquire_suboptimal.or(present_suboptimal)))
+    }
 }
 
 impl hal::Swapchain<Backend> for Swapchain {
@@ -500,6 +1726,15 @@ impl hal::Swapchain<Backend> for Swapchain {
         semaphore: Option<&native::Semaphore>,
         fence: Option<&native::Fence>,
     ) -> Result<(hal::SwapImageIndex, Option<hal::window::Suboptimal>), hal::AcquireError> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::trace_span!("acquire_image", timeout_ns);
+        #[cfg(feature = "tracing")]
+        let _guard = span.enter();
+
+        if self.recreate_pending || self.raw == vk::SwapchainKHR::null() {
+            return Err(hal::AcquireError::OutOfDate);
+        }
+
         let semaphore = semaphore.map_or(vk::Semaphore::null(), |s| s.0);
         let fence = fence.map_or(vk::Fence::null(), |f| f.0);
 
@@ -508,14 +1743,27 @@ impl hal::Swapchain<Backend> for Swapchain {
             .functor
             .acquire_next_image(self.raw, timeout_ns, semaphore, fence);
 
+        #[cfg(feature = "tracing")]
+        {
+            if let Ok((image_index, suboptimal)) = index {
+                tracing::trace!(image_index, suboptimal, "acquired");
+            }
+        }
+
         match index {
-            Ok((i, suboptimal)) => {
-                if suboptimal {
+            Ok((i, false)) => Ok((i, None)),
+            Ok((i, true)) => match self.suboptimal_policy {
+                SuboptimalPolicy::Report => Ok((i, Some(hal::window::Suboptimal))),
+                SuboptimalPolicy::AlwaysRecreate => {
+                    self.recreate_pending = true;
                     Ok((i, Some(hal::window::Suboptimal)))
-                } else {
+                }
+                SuboptimalPolicy::Ignore => Ok((i, None)),
+                SuboptimalPolicy::RecreateOnNextFrame => {
+                    self.recreate_pending = true;
                     Ok((i, None))
                 }
-            }
+            },
             Err(vk::Result::NOT_READY) => Err(hal::AcquireError::NotReady),
             Err(vk::Result::TIMEOUT) => Err(hal::AcquireError::Timeout),
             Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
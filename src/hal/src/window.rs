@@ -78,6 +78,15 @@ pub enum CreationError {
     /// Window in use
     #[fail(display = "{}", _0)]
     WindowInUse(device::WindowInUse),
+    /// The requested alpha composition mode isn't supported by the surface.
+    #[fail(display = "Unsupported composite alpha mode {:?}", _0)]
+    UnsupportedCompositeAlpha(CompositeAlpha),
+    /// The requested present mode isn't supported by the surface.
+    #[fail(display = "Unsupported present mode {:?}", _0)]
+    UnsupportedPresentMode(PresentMode),
+    /// The surface kept changing while the swapchain was being created.
+    #[fail(display = "Surface changed during swapchain creation")]
+    OutOfDate,
 }
 
 impl From<device::OutOfMemory> for CreationError {
@@ -170,6 +179,78 @@ pub struct SurfaceCapabilities {
     pub composite_alpha: CompositeAlpha,
 }
 
+impl SurfaceCapabilities {
+    /// Check if the surface can present images of the given extent without scaling.
+    ///
+    /// If the surface has a `current_extent`, only that extent is accepted. Otherwise
+    /// the extent must lie within `extents`, with both bounds treated as inclusive,
+    /// as reported by the backends.
+    pub fn accepts_extent(&self, extent: Extent2D) -> bool {
+        match self.current_extent {
+            Some(current) => current == extent,
+            None => {
+                extent.width >= self.extents.start.width
+                    && extent.height >= self.extents.start.height
+                    && extent.width <= self.extents.end.width
+                    && extent.height <= self.extents.end.height
+            }
+        }
+    }
+
+    /// Pick a sensible swapchain image count for the given present mode.
+    ///
+    /// `Mailbox` gets an extra image over the minimum, so the presentation engine can
+    /// drop frames without stalling rendering, while other modes use the minimum.
+    pub fn recommended_image_count(&self, present_mode: PresentMode) -> SwapImageIndex {
+        match present_mode {
+            PresentMode::Mailbox => (self.image_count.start + 1).min(self.image_count.end),
+            _ => self.image_count.start,
+        }
+    }
+
+    /// Pick the swapchain image count allowing `frames_in_flight` frames to be rendered
+    /// concurrently, clamped into the `image_count` range.
+    ///
+    /// One extra image is accounted for, since the presentation engine typically
+    /// holds on to the image currently on display.
+    pub fn image_count_for_latency(&self, frames_in_flight: SwapImageIndex) -> SwapImageIndex {
+        frames_in_flight
+            .saturating_add(1)
+            .max(self.image_count.start)
+            .min(self.image_count.end)
+    }
+
+    /// Check if a stereo swapchain, with one image layer per eye, can be created.
+    pub fn supports_stereo(&self) -> bool {
+        self.max_image_layers >= 2
+    }
+}
+
+impl fmt::Display for SurfaceCapabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "extents {}x{}..{}x{}",
+            self.extents.start.width,
+            self.extents.start.height,
+            self.extents.end.width,
+            self.extents.end.height,
+        )?;
+        if let Some(current) = self.current_extent {
+            write!(f, " (current {}x{})", current.width, current.height)?;
+        }
+        write!(
+            f,
+            ", images {}..{}, layers {}, usage {:?}, composite alpha {:?}",
+            self.image_count.start,
+            self.image_count.end,
+            self.max_image_layers,
+            self.usage,
+            self.composite_alpha,
+        )
+    }
+}
+
 /// A `Surface` abstracts the surface of a native window, which will be presented
 /// on the display.
 pub trait Surface<B: Backend>: fmt::Debug + Any + Send + Sync {
@@ -220,6 +301,46 @@ pub enum PresentMode {
     Relaxed = 3,
 }
 
+impl PresentMode {
+    /// Get the static characteristics of this present mode, e.g. for displaying in a settings UI.
+    pub fn describe(self) -> PresentModeInfo {
+        match self {
+            PresentMode::Immediate => PresentModeInfo {
+                tear_free: false,
+                may_drop_frames: false,
+                typical_latency_frames: 0,
+            },
+            PresentMode::Mailbox => PresentModeInfo {
+                tear_free: true,
+                may_drop_frames: true,
+                typical_latency_frames: 1,
+            },
+            PresentMode::Fifo => PresentModeInfo {
+                tear_free: true,
+                may_drop_frames: false,
+                typical_latency_frames: 2,
+            },
+            PresentMode::Relaxed => PresentModeInfo {
+                tear_free: false,
+                may_drop_frames: false,
+                typical_latency_frames: 2,
+            },
+        }
+    }
+}
+
+/// Characteristics of a present mode.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub struct PresentModeInfo {
+    /// Presented images never tear.
+    pub tear_free: bool,
+    /// Rendered images may be replaced before ever being displayed.
+    pub may_drop_frames: bool,
+    /// Approximate number of frames between presenting an image and it being displayed,
+    /// when rendering keeps up with the display.
+    pub typical_latency_frames: u8,
+}
+
 bitflags!(
     /// Specifies how the alpha channel of the images should be handled during
     /// compositing.
@@ -310,6 +431,9 @@ impl SwapchainConfig {
     /// Create a swapchain configuration based on the capabilities
     /// returned from a physical device query. If the surface does not
     /// specify a current size, default_extent is clamped and used instead.
+    ///
+    /// The image count is the one recommended for `Fifo`, see `with_mode_for_caps`
+    /// to pick a different present mode along with its recommended image count.
     pub fn from_caps(caps: &SurfaceCapabilities, format: Format, default_extent: Extent2D) -> Self {
         let clamped_extent = match caps.current_extent {
             Some(current) => current,
@@ -334,12 +458,14 @@ impl SwapchainConfig {
             unreachable!("neither INHERIT or OPAQUE CompositeAlpha modes are supported")
         };
 
+        let present_mode = PresentMode::Fifo;
+
         SwapchainConfig {
-            present_mode: PresentMode::Fifo,
+            present_mode,
             composite_alpha,
             format,
             extent: clamped_extent,
-            image_count: caps.image_count.start,
+            image_count: caps.recommended_image_count(present_mode),
             image_layers: 1,
             image_usage: image::Usage::COLOR_ATTACHMENT,
         }
@@ -357,6 +483,16 @@ impl SwapchainConfig {
         self
     }
 
+    /// Specify the presentation mode, and use the image count recommended for it
+    /// by `SurfaceCapabilities::recommended_image_count`.
+    ///
+    /// This is one image over the minimum for `Mailbox`, and the minimum otherwise.
+    pub fn with_mode_for_caps(mut self, caps: &SurfaceCapabilities, mode: PresentMode) -> Self {
+        self.present_mode = mode;
+        self.image_count = caps.recommended_image_count(mode);
+        self
+    }
+
     /// Specify the usage of backbuffer images.
     ///
     /// # Examples
@@ -482,3 +618,48 @@ pub trait Swapchain<B: Backend>: fmt::Debug + Any + Send + Sync {
         self.present::<_, B::Semaphore, _>(present_queue, image_index, iter::empty())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caps(extents: Range<Extent2D>, current_extent: Option<Extent2D>) -> SurfaceCapabilities {
+        SurfaceCapabilities {
+            image_count: 2..3,
+            current_extent,
+            extents,
+            max_image_layers: 1,
+            usage: image::Usage::COLOR_ATTACHMENT,
+            composite_alpha: CompositeAlpha::OPAQUE,
+        }
+    }
+
+    #[test]
+    fn test_accepts_fixed_current_extent() {
+        let extent = Extent2D { width: 800, height: 600 };
+        let caps = caps(extent..extent, Some(extent));
+        assert!(caps.accepts_extent(extent));
+        assert!(!caps.accepts_extent(Extent2D { width: 640, height: 480 }));
+    }
+
+    #[test]
+    fn test_accepts_extent_inclusive_bounds() {
+        let min = Extent2D { width: 1, height: 1 };
+        let max = Extent2D { width: 4096, height: 4096 };
+        let caps = caps(min..max, None);
+        assert!(caps.accepts_extent(min));
+        assert!(caps.accepts_extent(max));
+        assert!(!caps.accepts_extent(Extent2D { width: 4097, height: 1 }));
+    }
+
+    #[test]
+    fn test_image_count_for_mode() {
+        let extent = Extent2D { width: 800, height: 600 };
+        let caps = caps(extent..extent, Some(extent));
+        let config = SwapchainConfig::from_caps(&caps, Format::Bgra8Srgb, extent);
+        assert_eq!(config.image_count, 2);
+        let config = config.with_mode_for_caps(&caps, PresentMode::Mailbox);
+        assert_eq!(config.present_mode, PresentMode::Mailbox);
+        assert_eq!(config.image_count, 3);
+    }
+}